@@ -0,0 +1,15 @@
+// Native commands invoked from the Phoenix/LiveView frontend via
+// `window.__TAURI__.invoke("command_name", args)`. This is the bridge that
+// lets JS reach OS-native capabilities (clipboard, file dialogs,
+// notifications, ...) that Tauri exposes but the Phoenix HTTP layer cannot.
+//
+// UNLANDABLE in this tree (chunk0-3, second half): a Mix task generator hook
+// that lets users declare extra commands to fold into `generate_handler!`
+// would live in the `ex_tauri` Mix task templates, which aren't part of
+// this source tree — only the generated Rust side (this file and the
+// `invoke_handler` wiring in `main.rs`) is implemented here.
+
+#[tauri::command]
+pub fn greet(name: &str) -> String {
+    format!("Hello, {}! You've been greeted from Rust.", name)
+}
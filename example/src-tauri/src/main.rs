@@ -1,46 +1,253 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
+mod commands;
+
+use std::io::{BufRead, BufReader, Write};
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+#[cfg(windows)]
+use std::process::Stdio;
+#[cfg(not(windows))]
 use tauri::api::process::{Command, CommandEvent};
+use tauri::Manager;
+
+// Default number of consecutive sidecar restarts before we give up and tear
+// down the app rather than leaving a dead window open. Overridable via
+// `EX_TAURI_MAX_RESTART_ATTEMPTS` since how crash-tolerant a deployment
+// should be is a per-app call.
+const DEFAULT_MAX_RESTART_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+const MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(30);
+// A sidecar that stays up at least this long is considered healthy again,
+// so a restart right after counts as a fresh "consecutive" streak rather
+// than compounding a long-lived app's backoff/attempt count forever.
+const HEALTHY_UPTIME: std::time::Duration = std::time::Duration::from_secs(30);
+
+fn max_restart_attempts() -> u32 {
+    std::env::var("EX_TAURI_MAX_RESTART_ATTEMPTS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_MAX_RESTART_ATTEMPTS)
+}
 
 fn main() {
     tauri::Builder::default()
-        .setup(|_app| {
-            start_server();
-            check_server_started();
+        .invoke_handler(tauri::generate_handler![commands::greet])
+        .setup(|app| {
+            start_server(app.handle());
+            check_server_started()?;
             Ok(())
         })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
-fn start_server() {
-    tauri::async_runtime::spawn(async move {
-        let (mut rx, mut _child) = Command::new_sidecar("desktop")
-            .expect("failed to setup `desktop` sidecar")
+
+// `windows_subsystem = "windows"` above only covers the Tauri binary, not
+// the spawned `desktop` sidecar, which still pops a console window on
+// Windows. `tauri::api::process::Command` (used on other platforms for its
+// `CommandEvent` stream and bundled-resource resolution) has no way to set
+// process creation flags, so on Windows we bypass it and spawn the bundled
+// sidecar binary directly via `std::process::Command`, trading away the
+// `CommandEvent` stream for `CREATE_NO_WINDOW`.
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+// Locates the bundled sidecar binary next to the running executable, using
+// Tauri's own `<name>-<target-triple>[.exe]` sidecar naming convention.
+#[cfg(windows)]
+fn sidecar_path(name: &str) -> std::path::PathBuf {
+    let triple =
+        tauri::utils::platform::target_triple().expect("failed to resolve target triple");
+    let exe_dir = std::env::current_exe()
+        .expect("failed to resolve current exe")
+        .parent()
+        .expect("current exe has no parent directory")
+        .to_path_buf();
+    exe_dir.join(format!("{}-{}.exe", name, triple))
+}
+
+// Spawns the `desktop` sidecar with no console window, pumps its stdout and
+// stderr on dedicated threads, and blocks (off the async executor, via
+// `spawn_blocking`) until it exits.
+#[cfg(windows)]
+async fn run_sidecar_once() -> Option<i32> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let mut child = std::process::Command::new(sidecar_path("desktop"))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .creation_flags(CREATE_NO_WINDOW)
             .spawn()
             .expect("Failed to spawn packaged node");
 
-        while let Some(event) = rx.recv().await {
-            if let CommandEvent::Stdout(line) = event {
+        let stdout = child.stdout.take().expect("sidecar stdout not piped");
+        let stderr = child.stderr.take().expect("sidecar stderr not piped");
+        let stdout_pump = std::thread::spawn(move || {
+            for line in std::io::BufRead::lines(std::io::BufReader::new(stdout)).flatten() {
                 println!("{}", line);
             }
+        });
+        let stderr_pump = std::thread::spawn(move || {
+            for line in std::io::BufRead::lines(std::io::BufReader::new(stderr)).flatten() {
+                eprintln!("{}", line);
+            }
+        });
+
+        let status = child.wait().expect("failed to wait on desktop sidecar");
+        let _ = stdout_pump.join();
+        let _ = stderr_pump.join();
+        eprintln!("desktop sidecar terminated with code {:?}", status.code());
+        status.code()
+    })
+    .await
+    .expect("sidecar supervision thread panicked")
+}
+
+#[cfg(not(windows))]
+async fn run_sidecar_once() -> Option<i32> {
+    let (mut rx, mut _child) = Command::new_sidecar("desktop")
+        .expect("failed to setup `desktop` sidecar")
+        .spawn()
+        .expect("Failed to spawn packaged node");
+
+    let mut exit_code = None;
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line) => println!("{}", line),
+            CommandEvent::Stderr(line) => eprintln!("{}", line),
+            CommandEvent::Error(err) => eprintln!("desktop sidecar error: {}", err),
+            CommandEvent::Terminated(payload) => {
+                exit_code = payload.code;
+                eprintln!("desktop sidecar terminated with code {:?}", exit_code);
+            }
+            _ => {}
+        }
+    }
+    exit_code
+}
+
+// Supervises the `desktop` sidecar: forwards its stdout/stderr, and restarts
+// it with an exponential backoff if the Elixir release crashes, instead of
+// leaving the window open with a dead backend.
+fn start_server(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let max_attempts = max_restart_attempts();
+        let mut attempt = 0;
+        let mut backoff = INITIAL_BACKOFF;
+
+        loop {
+            let started_at = std::time::Instant::now();
+            let exit_code = run_sidecar_once().await;
+
+            // Exit code 0 means the Elixir release stopped on its own (e.g.
+            // the app is shutting down), not that it crashed — don't spawn a
+            // fresh release behind a closing window.
+            if exit_code == Some(0) {
+                eprintln!("desktop sidecar exited cleanly, not restarting");
+                return;
+            }
+
+            if started_at.elapsed() >= HEALTHY_UPTIME {
+                attempt = 0;
+                backoff = INITIAL_BACKOFF;
+            }
+
+            attempt += 1;
+            if attempt > max_attempts {
+                eprintln!(
+                    "desktop sidecar failed {} times in a row, giving up",
+                    attempt
+                );
+                let _ = app_handle.emit_all("sidecar-crashed", exit_code);
+                app_handle.exit(1);
+                return;
+            }
+
+            eprintln!(
+                "restarting desktop sidecar (attempt {}/{}) in {:?}",
+                attempt, max_attempts, backoff
+            );
+            // `tauri::async_runtime` exposes no timer of its own, so offload
+            // the sleep to the blocking pool rather than parking an async
+            // worker thread for up to `MAX_BACKOFF`.
+            let sleep_for = backoff;
+            tauri::async_runtime::spawn_blocking(move || std::thread::sleep(sleep_for))
+                .await
+                .expect("backoff sleep task panicked");
+            backoff = std::cmp::min(backoff * 2, MAX_BACKOFF);
         }
     });
 }
 
-fn check_server_started() {
-    let sleep_interval = std::time::Duration::from_secs(1);
-    let host = "localhost".to_string();
-    let port = "4000".to_string();
+// Polls the Phoenix endpoint with a real HTTP request rather than a bare TCP
+// connect, since the listener binds well before the app can actually serve a
+// response (assets still compiling, endpoint still starting up).
+fn check_server_started() -> Result<(), String> {
+    let host = std::env::var("EX_TAURI_DEV_HOST").unwrap_or_else(|_| "localhost".to_string());
+    let port = std::env::var("EX_TAURI_DEV_PORT").unwrap_or_else(|_| "4000".to_string());
     let addr = format!("{}:{}", host, port);
+    let sleep_interval = std::time::Duration::from_secs(1);
+    let total_timeout = std::time::Duration::from_secs(60);
+    let started_at = std::time::Instant::now();
+
     println!(
         "Waiting for your phoenix dev server to start on {}...",
         addr
     );
+
     loop {
-        if std::net::TcpStream::connect(addr.clone()).is_ok() {
-           break;
+        if http_get_ok(&host, &addr) {
+            return Ok(());
+        }
+
+        if started_at.elapsed() >= total_timeout {
+            return Err(format!(
+                "timed out after {:?} waiting for the phoenix dev server at {} to respond",
+                total_timeout, addr
+            ));
         }
+
         std::thread::sleep(sleep_interval);
     }
 }
 
+// How long a single GET is allowed to hang before we give up on this poll
+// attempt and try again; without this, a connection that's accepted but
+// never answered (assets still compiling) can block past `total_timeout`.
+const SOCKET_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Issues a bare-bones `GET /` over a raw `TcpStream` and treats the server as
+// ready only on a successful (2xx/3xx) status line — a 500 while assets are
+// still compiling is not "ready". Reads only the status line rather than the
+// whole body, since `read_to_string` both wastes time on a chunked/streamed
+// response and fails outright on a non-UTF-8 body.
+fn http_get_ok(host: &str, addr: &str) -> bool {
+    let mut stream = match std::net::TcpStream::connect(addr) {
+        Ok(stream) => stream,
+        Err(_) => return false,
+    };
+    if stream.set_read_timeout(Some(SOCKET_TIMEOUT)).is_err()
+        || stream.set_write_timeout(Some(SOCKET_TIMEOUT)).is_err()
+    {
+        return false;
+    }
+
+    let request = format!(
+        "GET / HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        host
+    );
+    if stream.write_all(request.as_bytes()).is_err() {
+        return false;
+    }
+
+    let mut status_line = String::new();
+    if BufReader::new(stream).read_line(&mut status_line).is_err() {
+        return false;
+    }
+
+    status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .is_some_and(|code| (200..400).contains(&code))
+}
+